@@ -1,17 +1,315 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use futures::stream::{self, Stream};
 use serde::ser::SerializeMap;
 use serde::{Deserialize, Serialize, Serializer};
-// use std::collections::HashMap;
+use std::collections::HashMap;
+use std::future::Future;
+use std::rc::Rc;
+use url::Url;
+
 use crate::{CountryCode, Secret};
 
+/// The request fields to perform a list `institutions` request.
+#[derive(Clone, Serialize)]
+pub struct ListInstitutionsRequest {
+    /// Plaid Client ID
+    pub client_id: String,
+
+    /// Plaid API Secret
+    pub secret: Secret,
+
+    /// The total number of institutions to return.
+    pub count: u16,
+
+    /// The number of institutions to skip before returning results.
+    pub offset: u32,
+
+    /// Specify an array of Plaid-supported country codes this institution supports, using the
+    /// ISO-3166-1 alpha-2 country code standard.
+    pub country_codes: Vec<CountryCode>,
+
+    /// Specifies optional parameters for /institutions/get. If provided, must not be null.
+    pub options: ListInstitutionsOptions,
+}
+
+/// Specifies optional parameters for `/institutions/get`, in the style of up-api's
+/// `ListTransactionsOptions`: fields are left unset by default and populated fluently.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ListInstitutionsOptions {
+    /// Filters institutions to only those that support all of the given products.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    products: Option<Vec<String>>,
+
+    /// Filters institutions to only those whose `routing_numbers` contain one of the given
+    /// routing numbers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    routing_numbers: Option<Vec<String>>,
+
+    /// When true, return an institution's logo, brand color, and URL. The default value is
+    /// false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    include_optional_metadata: Option<bool>,
+
+    /// When true, return information about whether an institution has an OAuth login flow.
+    /// The default value is false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    oauth: Option<bool>,
+}
+
+impl ListInstitutionsOptions {
+    /// Creates an empty set of options, equivalent to omitting `options` entirely.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Filters institutions down to only those supporting every product in `products`.
+    pub fn products(mut self, products: Vec<String>) -> Self {
+        self.products = Some(products);
+        self
+    }
+
+    /// Filters institutions down to only those whose `routing_numbers` contain one of
+    /// `routing_numbers`.
+    pub fn routing_numbers(mut self, routing_numbers: Vec<String>) -> Self {
+        self.routing_numbers = Some(routing_numbers);
+        self
+    }
+
+    /// Requests the institution's logo, brand color, and URL.
+    pub fn include_optional_metadata(mut self, include: bool) -> Self {
+        self.include_optional_metadata = Some(include);
+        self
+    }
+
+    /// Requests whether each institution has an OAuth login flow.
+    pub fn oauth(mut self, oauth: bool) -> Self {
+        self.oauth = Some(oauth);
+        self
+    }
+}
+
 /// The response from performing a list `institutions` request.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ListInstitutionsResponse {
     /// The financial institution accounts associated with the Item.
     #[serde(default)]
     institutions: Vec<Institution>,
+
+    /// The total number of institutions available, which can be used to determine how many
+    /// pages of results remain.
+    total: u32,
+
     request_id: String,
 }
 
+impl ListInstitutionsResponse {
+    /// Public getter for `institutions`.
+    pub fn institutions(&self) -> Vec<Institution> {
+        self.institutions.clone()
+    }
+
+    /// Public getter for `total`.
+    pub fn total(&self) -> u32 {
+        self.total
+    }
+}
+
+/// The ways [`list_institutions_stream`] can fail.
+#[derive(Debug)]
+pub enum ListInstitutionsStreamError<E> {
+    /// `fetch_page` itself returned an error.
+    FetchFailed(E),
+
+    /// A page reported zero institutions while `offset` was still short of `total`, so the
+    /// request could never make further progress. This can happen if `request.count` is zero,
+    /// or if the server returns an empty or inconsistent page.
+    NoProgress,
+}
+
+/// Repeatedly issues `fetch_page` with an advancing `offset`, yielding every [`Institution`]
+/// across all pages until `offset + returned >= total`. `fetch_page` is expected to wrap a call
+/// to `/institutions/get` for the given request, so callers never have to manage page math
+/// themselves.
+pub fn list_institutions_stream<F, Fut, E>(
+    request: ListInstitutionsRequest,
+    fetch_page: F,
+) -> impl Stream<Item = Result<Institution, ListInstitutionsStreamError<E>>>
+where
+    F: Fn(ListInstitutionsRequest) -> Fut,
+    Fut: Future<Output = Result<ListInstitutionsResponse, E>>,
+{
+    let fetch_page = Rc::new(fetch_page);
+    stream::unfold(PageState::Fetch(request), move |mut state| {
+        let fetch_page = Rc::clone(&fetch_page);
+        async move {
+            loop {
+                match state {
+                    PageState::Done => return None,
+                    PageState::Drain {
+                        request,
+                        total,
+                        mut remaining,
+                    } => match remaining.next() {
+                        Some(institution) => {
+                            return Some((
+                                Ok(institution),
+                                PageState::Drain {
+                                    request,
+                                    total,
+                                    remaining,
+                                },
+                            ));
+                        }
+                        None if request.offset >= total => return None,
+                        None => state = PageState::Fetch(request),
+                    },
+                    PageState::Fetch(request) => {
+                        let response = match fetch_page(request.clone()).await {
+                            Ok(response) => response,
+                            Err(error) => {
+                                return Some((
+                                    Err(ListInstitutionsStreamError::FetchFailed(error)),
+                                    PageState::Done,
+                                ));
+                            }
+                        };
+
+                        let total = response.total();
+                        let returned = response.institutions.len() as u32;
+                        let mut next_request = request;
+                        next_request.offset += returned;
+
+                        if returned == 0 && next_request.offset < total {
+                            return Some((
+                                Err(ListInstitutionsStreamError::NoProgress),
+                                PageState::Done,
+                            ));
+                        }
+
+                        let mut remaining = response.institutions.into_iter();
+
+                        state = match remaining.next() {
+                            Some(institution) => {
+                                return Some((
+                                    Ok(institution),
+                                    PageState::Drain {
+                                        request: next_request,
+                                        total,
+                                        remaining,
+                                    },
+                                ));
+                            }
+                            None if next_request.offset >= total => PageState::Done,
+                            None => PageState::Fetch(next_request),
+                        };
+                    }
+                }
+            }
+        }
+    })
+}
+
+enum PageState {
+    Fetch(ListInstitutionsRequest),
+    Drain {
+        request: ListInstitutionsRequest,
+        total: u32,
+        remaining: std::vec::IntoIter<Institution>,
+    },
+    Done,
+}
+
+#[cfg(test)]
+mod list_institutions_stream_tests {
+    use super::*;
+    use futures::StreamExt;
+    use std::cell::RefCell;
+
+    fn request(offset: u32) -> ListInstitutionsRequest {
+        ListInstitutionsRequest {
+            client_id: "client-id".to_string(),
+            secret: "secret".to_string().into(),
+            count: 2,
+            offset,
+            country_codes: vec![],
+            options: ListInstitutionsOptions::new(),
+        }
+    }
+
+    fn response(total: u32, institution_ids: &[&str]) -> ListInstitutionsResponse {
+        ListInstitutionsResponse {
+            institutions: institution_ids.iter().map(|id| institution(id)).collect(),
+            total,
+            request_id: "request-id".to_string(),
+        }
+    }
+
+    fn institution(id: &str) -> Institution {
+        Institution {
+            institution_id: id.to_string(),
+            name: id.to_string(),
+            products: vec![],
+            country_codes: vec![],
+            url: None,
+            primary_color: None,
+            logo: None,
+            routing_numbers: vec![],
+            oauth: false,
+            status: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn drains_institutions_across_multiple_pages() {
+        let pages = RefCell::new(
+            vec![
+                response(5, &["a", "b"]),
+                response(5, &["c", "d"]),
+                response(5, &["e"]),
+            ]
+            .into_iter(),
+        );
+        let stream = list_institutions_stream(request(0), move |_request| {
+            let next = pages.borrow_mut().next();
+            async move { next.ok_or(()) }
+        });
+
+        let ids: Vec<String> = stream.map(|result| result.unwrap().id()).collect().await;
+        assert_eq!(ids, vec!["a", "b", "c", "d", "e"]);
+    }
+
+    #[tokio::test]
+    async fn stops_with_no_progress_error_on_a_stalled_page() {
+        let pages = RefCell::new(vec![response(5, &[])].into_iter());
+        let stream = list_institutions_stream(request(0), move |_request| {
+            let next = pages.borrow_mut().next();
+            async move { next.ok_or(()) }
+        });
+
+        let results: Vec<_> = stream.collect().await;
+        assert_eq!(results.len(), 1);
+        assert!(matches!(
+            results[0],
+            Err(ListInstitutionsStreamError::NoProgress)
+        ));
+    }
+
+    #[tokio::test]
+    async fn surfaces_fetch_page_errors() {
+        let stream = list_institutions_stream(request(0), |_request| async {
+            Err::<ListInstitutionsResponse, &str>("boom")
+        });
+
+        let results: Vec<_> = stream.collect().await;
+        assert_eq!(results.len(), 1);
+        assert!(matches!(
+            results[0],
+            Err(ListInstitutionsStreamError::FetchFailed("boom"))
+        ));
+    }
+}
+
 /// The request fields to perform a get `institution`
 #[derive(Serialize)]
 pub struct GetInstitutionRequest {
@@ -73,6 +371,66 @@ pub enum InstitutionOption {
     IncludeStatus,
 }
 
+/// The request fields to perform a search `institutions` request.
+#[derive(Serialize)]
+pub struct SearchInstitutionsRequest {
+    /// The search query. Must be at least one character.
+    pub query: String,
+
+    /// Filter institutions to only those supporting all of the given products.
+    pub products: Vec<String>,
+
+    /// Plaid Client ID
+    pub client_id: String,
+
+    /// Plaid API Secret
+    pub secret: Secret,
+
+    /// Specify an array of Plaid-supported country codes this institution supports, using the
+    /// ISO-3166-1 alpha-2 country code standard.
+    pub country_codes: Vec<CountryCode>,
+
+    /// Specifies optional parameters for /institutions/search. If provided, must not be null.
+    #[serde(serialize_with = "serialize_options")]
+    pub options: Vec<InstitutionOption>,
+}
+
+/// The response from performing a search `institutions` request.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SearchInstitutionsResponse {
+    /// The institutions matching `query`.
+    #[serde(default)]
+    institutions: Vec<Institution>,
+    request_id: String,
+}
+
+impl SearchInstitutionsResponse {
+    /// Public getter for `institutions`.
+    pub fn institutions(&self) -> Vec<Institution> {
+        self.institutions.clone()
+    }
+}
+
+/// Indexes a list of institutions by their `routing_numbers`, mirroring how mercury-rust
+/// structures `ElectronicRoutingInfo`, so callers can resolve an ABA routing number to candidate
+/// institutions without another round trip. Since Plaid's per-institution `routing_numbers` list
+/// is explicitly "not comprehensive," a routing number may be absent even for an institution that
+/// actually uses it, and a single routing number may resolve to more than one institution.
+pub fn index_by_routing_number(institutions: &[Institution]) -> HashMap<String, Vec<Institution>> {
+    let mut index: HashMap<String, Vec<Institution>> = HashMap::new();
+
+    for institution in institutions {
+        for routing_number in &institution.routing_numbers {
+            index
+                .entry(routing_number.clone())
+                .or_default()
+                .push(institution.clone());
+        }
+    }
+
+    index
+}
+
 /// Metadata about a requested `Institution`.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Institution {
@@ -105,7 +463,7 @@ pub struct Institution {
 
     ///Indicates that the institution has an OAuth login flow. This is primarily relevant to
     ///institutions with European country codes.
-    // oauth: bool,
+    oauth: bool,
 
     ///The status of an institution is determined by the health of its Item logins, Transactions
     ///updates, Auth requests, Balance requests, and Identity requests. A login attempt is conducted
@@ -115,9 +473,7 @@ pub struct Institution {
     ///Institution status is accessible in the Dashboard and via the API using
     ///the /institutions/get_by_id endpoint with the include_status option set to true. Note that
     ///institution status is not available in the Sandbox environment.
-    oauth: bool,
-    // TODO: finish this
-    // status: HashMap<String, Status>,
+    status: Option<InstitutionStatus>,
 }
 
 impl Institution {
@@ -136,9 +492,255 @@ impl Institution {
         self.logo.clone()
     }
 
-    /// Public getter for Plaid's instituion `url`.
-    pub fn url(&self) -> Option<String> {
-        self.url.clone()
+    /// Public getter for Plaid's institution `oauth` flag.
+    pub fn oauth(&self) -> bool {
+        self.oauth
+    }
+
+    /// Public getter for Plaid's instituion `url`, parsed into a [`Url`]. `None` if Plaid didn't
+    /// return a URL, or if the returned value failed to parse.
+    pub fn url(&self) -> Option<Url> {
+        self.url.as_deref().and_then(|url| Url::parse(url).ok())
+    }
+
+    /// Public getter for Plaid's institution `status`. `None` when Plaid returned null, which
+    /// happens whenever `IncludeStatus` wasn't requested, there isn't enough traffic to
+    /// calculate a status, or the institution was fetched from the Sandbox environment.
+    pub fn status(&self) -> Option<InstitutionStatus> {
+        self.status.clone()
+    }
+
+    /// Decodes `logo` into the raw bytes of the 152x152 PNG Plaid returns. `None` if Plaid
+    /// didn't return a logo, or if the returned value wasn't valid base64.
+    pub fn logo_png_bytes(&self) -> Option<Vec<u8>> {
+        self.logo
+            .as_deref()
+            .and_then(|logo| BASE64.decode(logo).ok())
+    }
+
+    /// Parses `primary_color`'s `#RRGGBB` hex (tolerating a missing leading `#`) into its
+    /// red, green, and blue components. `None` if Plaid didn't return a color, or if the
+    /// returned value wasn't a valid 6-digit hex color.
+    pub fn primary_color_rgb(&self) -> Option<(u8, u8, u8)> {
+        let hex = self.primary_color.as_deref()?.trim_start_matches('#');
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+
+        let red = u8::from_str_radix(&hex[0..2], 16).ok()?;
+        let green = u8::from_str_radix(&hex[2..4], 16).ok()?;
+        let blue = u8::from_str_radix(&hex[4..6], 16).ok()?;
+        Some((red, green, blue))
+    }
+}
+
+#[cfg(test)]
+mod institution_tests {
+    use super::*;
+
+    fn institution_with(primary_color: Option<&str>, logo: Option<&str>) -> Institution {
+        Institution {
+            institution_id: "ins_1".to_string(),
+            name: "Test Bank".to_string(),
+            products: vec![],
+            country_codes: vec![],
+            url: None,
+            primary_color: primary_color.map(str::to_string),
+            logo: logo.map(str::to_string),
+            routing_numbers: vec![],
+            oauth: false,
+            status: None,
+        }
+    }
+
+    #[test]
+    fn primary_color_rgb_parses_a_leading_hash() {
+        let institution = institution_with(Some("#1A2B3C"), None);
+        assert_eq!(institution.primary_color_rgb(), Some((0x1A, 0x2B, 0x3C)));
+    }
+
+    #[test]
+    fn primary_color_rgb_tolerates_a_missing_hash() {
+        let institution = institution_with(Some("1A2B3C"), None);
+        assert_eq!(institution.primary_color_rgb(), Some((0x1A, 0x2B, 0x3C)));
+    }
+
+    #[test]
+    fn primary_color_rgb_is_none_when_plaid_omitted_it() {
+        let institution = institution_with(None, None);
+        assert_eq!(institution.primary_color_rgb(), None);
+    }
+
+    #[test]
+    fn primary_color_rgb_rejects_the_wrong_length() {
+        let institution = institution_with(Some("#1A2B3"), None);
+        assert_eq!(institution.primary_color_rgb(), None);
+    }
+
+    #[test]
+    fn primary_color_rgb_rejects_non_ascii_input_instead_of_panicking() {
+        // Regression test for 4e08f2a: "é1234" is 6 bytes but only 5 chars, so slicing by byte
+        // range used to panic on a non-char-boundary index instead of returning `None`.
+        let institution = institution_with(Some("é1234"), None);
+        assert_eq!(institution.primary_color_rgb(), None);
+    }
+
+    #[test]
+    fn logo_png_bytes_decodes_valid_base64() {
+        let institution = institution_with(None, Some("aGVsbG8="));
+        assert_eq!(institution.logo_png_bytes(), Some(b"hello".to_vec()));
+    }
+
+    #[test]
+    fn logo_png_bytes_is_none_for_invalid_base64() {
+        let institution = institution_with(None, Some("not valid base64!"));
+        assert_eq!(institution.logo_png_bytes(), None);
+    }
+
+    #[test]
+    fn logo_png_bytes_is_none_when_plaid_omitted_it() {
+        let institution = institution_with(None, None);
+        assert_eq!(institution.logo_png_bytes(), None);
+    }
+}
+
+/// The health of an institution's Item logins, Transactions updates, Auth requests, Identity
+/// requests, Investments updates, and Liabilities updates, as returned when `IncludeStatus` is
+/// passed on a get `institution` request.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct InstitutionStatus {
+    /// Status of Item logins for the institution.
+    item_logins: Option<ProductStatus>,
+
+    /// Status of Transactions updates for the institution.
+    transactions_updates: Option<ProductStatus>,
+
+    /// Status of Auth requests for the institution.
+    auth: Option<ProductStatus>,
+
+    /// Status of Identity requests for the institution.
+    identity: Option<ProductStatus>,
+
+    /// Status of Investments updates for the institution.
+    investments_updates: Option<ProductStatus>,
+
+    /// Status of Liabilities updates for the institution.
+    liabilities_updates: Option<ProductStatus>,
+}
+
+impl InstitutionStatus {
+    /// Public getter for the `item_logins` status.
+    pub fn item_logins(&self) -> Option<ProductStatus> {
+        self.item_logins.clone()
+    }
+
+    /// Public getter for the `transactions_updates` status.
+    pub fn transactions_updates(&self) -> Option<ProductStatus> {
+        self.transactions_updates.clone()
+    }
+
+    /// Public getter for the `auth` status.
+    pub fn auth(&self) -> Option<ProductStatus> {
+        self.auth.clone()
+    }
+
+    /// Public getter for the `identity` status.
+    pub fn identity(&self) -> Option<ProductStatus> {
+        self.identity.clone()
+    }
+
+    /// Public getter for the `investments_updates` status.
+    pub fn investments_updates(&self) -> Option<ProductStatus> {
+        self.investments_updates.clone()
+    }
+
+    /// Public getter for the `liabilities_updates` status.
+    pub fn liabilities_updates(&self) -> Option<ProductStatus> {
+        self.liabilities_updates.clone()
+    }
+}
+
+/// The status of a single Plaid product for an institution.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ProductStatus {
+    /// The status of the product.
+    status: HealthIndicator,
+
+    /// The timestamp (ISO 8601) of the last change to `status`.
+    last_status_change: String,
+
+    /// Percentage breakdown of health check results for the product over the past 7 days.
+    breakdown: StatusBreakdown,
+}
+
+impl ProductStatus {
+    /// Public getter for the `status`.
+    pub fn status(&self) -> HealthIndicator {
+        self.status.clone()
+    }
+
+    /// Public getter for `last_status_change`.
+    pub fn last_status_change(&self) -> String {
+        self.last_status_change.clone()
+    }
+
+    /// Public getter for the `breakdown`.
+    pub fn breakdown(&self) -> StatusBreakdown {
+        self.breakdown.clone()
+    }
+}
+
+/// A coarse health indicator reported by Plaid for a given product at an institution.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum HealthIndicator {
+    /// The product is healthy at this institution.
+    Healthy,
+
+    /// The product is experiencing a degradation at this institution.
+    Degraded,
+
+    /// The product is down at this institution.
+    Down,
+}
+
+/// Percentage breakdown of health check results for a product over the past 7 days. The
+/// percentages are calculated using all request counts, and will sum to roughly 100%.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StatusBreakdown {
+    /// The percentage of requests that were successful.
+    success: f64,
+
+    /// The percentage of requests that failed due to a Plaid error.
+    error_plaid: f64,
+
+    /// The percentage of requests that failed due to an institution error.
+    error_institution: f64,
+
+    /// The average time, in seconds, that it takes a refresh to complete. Only present for the
+    /// `item_logins` and `transactions_updates` breakdowns.
+    refresh_interval: Option<f64>,
+}
+
+impl StatusBreakdown {
+    /// Public getter for `success`.
+    pub fn success(&self) -> f64 {
+        self.success
+    }
+
+    /// Public getter for `error_plaid`.
+    pub fn error_plaid(&self) -> f64 {
+        self.error_plaid
+    }
+
+    /// Public getter for `error_institution`.
+    pub fn error_institution(&self) -> f64 {
+        self.error_institution
+    }
+
+    /// Public getter for `refresh_interval`.
+    pub fn refresh_interval(&self) -> Option<f64> {
+        self.refresh_interval
     }
 }
 